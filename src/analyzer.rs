@@ -1,3 +1,4 @@
+use anyhow::Context;
 use time::OffsetDateTime;
 
 use aws_sdk_iam::{
@@ -9,10 +10,12 @@ use crate::finding::{
     duration_gt_age, Finding, FindingDetails, FindingType, ResourceType, UnusedIamRoleDetails,
     UnusedIamUserAccessKeyDetails, UnusedIamUserPasswordDetails, UnusedPermissionDetails,
 };
+use crate::ignore::IgnoreConfig;
 
 pub struct MetaData {
     pub unused_access_age: i64,
     pub owner_account: String,
+    pub ignore_config: IgnoreConfig,
 }
 
 impl MetaData {
@@ -63,6 +66,9 @@ impl MetaData {
             if role.path().starts_with("/aws-service-role/") {
                 continue; // ignore service role
             }
+            if self.ignore_config.ignores_resource(&role.arn, role.path(), &role.role_name) {
+                continue;
+            }
             if duration_gt_age(Some(role.create_date), &now, self.unused_access_age) {
                 result.append(&mut self.analyze_role(iam_client, role.role_name()).await?);
             }
@@ -72,6 +78,10 @@ impl MetaData {
     }
 
     async fn analyze_user(&self, iam_client: &Client, user: User) -> anyhow::Result<Vec<Finding>> {
+        if self.ignore_config.ignores_resource(&user.arn, user.path(), &user.user_name) {
+            return Ok(vec![]);
+        }
+
         let now = OffsetDateTime::now_utc();
         let mut result = vec![];
         let login_profile = {
@@ -95,6 +105,8 @@ impl MetaData {
                         UnusedIamUserPasswordDetails { last_accessed: detail },
                     )],
                     finding_type: FindingType::UnusedIamUserPassword,
+                    remediation: None,
+                    diff_status: None,
                 })
             }
         }
@@ -136,6 +148,8 @@ impl MetaData {
                 id: uuid::Uuid::new_v4().to_string(),
                 finding_details: unused_access_key_details,
                 finding_type: FindingType::UnusedIamUserAccessKey,
+                remediation: None,
+                diff_status: None,
             })
         }
 
@@ -143,12 +157,7 @@ impl MetaData {
             .get_last_accessed(iam_client, &user.arn)
             .await?
             .into_iter()
-            .filter_map(|last_accessed| {
-                let details = Into::<UnusedPermissionDetails>::into(last_accessed);
-                details
-                    .any_not_used(&now, self.unused_access_age)
-                    .then_some(FindingDetails::UnusedPermissionDetails(details))
-            })
+            .filter_map(|last_accessed| self.filter_unused_permission(last_accessed))
             .collect();
         if !unused_permission_details.is_empty() {
             result.push(Finding {
@@ -158,6 +167,8 @@ impl MetaData {
                 id: uuid::Uuid::new_v4().to_string(),
                 finding_details: unused_permission_details,
                 finding_type: FindingType::UnusedPermission,
+                remediation: None,
+                diff_status: None,
             })
         }
 
@@ -183,6 +194,8 @@ impl MetaData {
                     last_accessed: detail,
                 })],
                 finding_type: FindingType::UnusedIamRole,
+                remediation: None,
+                diff_status: None,
             })
         }
 
@@ -190,12 +203,7 @@ impl MetaData {
             .get_last_accessed(iam_client, &role.arn)
             .await?
             .into_iter()
-            .filter_map(|last_accessed| {
-                let details = Into::<UnusedPermissionDetails>::into(last_accessed);
-                details
-                    .any_not_used(&now, self.unused_access_age)
-                    .then_some(FindingDetails::UnusedPermissionDetails(details))
-            })
+            .filter_map(|last_accessed| self.filter_unused_permission(last_accessed))
             .collect();
         if !unused_permission_details.is_empty() {
             result.push(Finding {
@@ -205,12 +213,32 @@ impl MetaData {
                 id: uuid::Uuid::new_v4().to_string(),
                 finding_details: unused_permission_details,
                 finding_type: FindingType::UnusedPermission,
+                remediation: None,
+                diff_status: None,
             })
         }
 
         Ok(result)
     }
 
+    /// Converts a raw access advisor entry into a `FindingDetails::UnusedPermissionDetails`,
+    /// dropping any action ignored by `ignore_config` and the whole entry if its service
+    /// namespace is ignored or nothing about it is actually unused.
+    fn filter_unused_permission(&self, last_accessed: ServiceLastAccessed) -> Option<FindingDetails> {
+        let now = OffsetDateTime::now_utc();
+        let mut details = Into::<UnusedPermissionDetails>::into(last_accessed);
+        if self.ignore_config.ignores_permission(&details.service_namespace, "") {
+            return None;
+        }
+        if let Some(actions) = &mut details.actions {
+            let service_namespace = details.service_namespace.clone();
+            actions.retain(|action| !self.ignore_config.ignores_permission(&service_namespace, &action.action));
+        }
+        details
+            .any_not_used(&now, self.unused_access_age)
+            .then_some(FindingDetails::UnusedPermissionDetails(details))
+    }
+
     async fn get_last_accessed(&self, iam_client: &Client, arn: &str) -> anyhow::Result<Vec<ServiceLastAccessed>> {
         let job_id = {
             iam_client
@@ -242,3 +270,93 @@ impl MetaData {
         anyhow::bail!("timeout");
     }
 }
+
+/// Assumes `role_arn` in a member account and runs the full analysis against it, using the
+/// account ID embedded in the role ARN as `resource_owner_account` for every finding produced.
+async fn analyze_assumed_account(
+    sts_client: &aws_sdk_sts::Client,
+    sdk_config: &aws_config::SdkConfig,
+    role_arn: &str,
+    unused_access_age: i64,
+    ignore_config: IgnoreConfig,
+) -> anyhow::Result<Vec<Finding>> {
+    let owner_account = role_arn
+        .split(':')
+        .nth(4)
+        .filter(|s| !s.is_empty())
+        .ok_or(anyhow::anyhow!("invalid role arn: {role_arn}"))?
+        .to_string();
+
+    let assumed = sts_client
+        .assume_role()
+        .role_arn(role_arn)
+        .role_session_name("aws-unused-analyzer")
+        .send()
+        .await
+        .with_context(|| format!("assume_role failed for {role_arn}"))?;
+    let creds = assumed
+        .credentials
+        .ok_or(anyhow::anyhow!("assume_role for {role_arn} returned no credentials"))?;
+    let credentials_provider = aws_credential_types::Credentials::new(
+        creds.access_key_id,
+        creds.secret_access_key,
+        Some(creds.session_token),
+        None,
+        "AssumeRole",
+    );
+
+    let iam_client = aws_sdk_iam::Client::from_conf(
+        aws_sdk_iam::config::Builder::from(sdk_config)
+            .credentials_provider(credentials_provider)
+            .build(),
+    );
+
+    MetaData {
+        unused_access_age,
+        owner_account,
+        ignore_config,
+    }
+    .analyze(&iam_client)
+    .await
+    .with_context(|| format!("failed to scan account for role {role_arn} after assuming it"))
+}
+
+/// Runs the analyzer against every account reachable via `role_arns`, assuming each role with
+/// bounded concurrency and aggregating the resulting findings. Accounts that fail to assume or
+/// fail partway through the scan (throttling, missing permissions, ...) are reported as a warning
+/// and otherwise skipped, rather than failing the whole run; the error message distinguishes the
+/// two via the `with_context` wrapping in `analyze_assumed_account`.
+pub async fn analyze_multi_account(
+    sts_client: &aws_sdk_sts::Client,
+    sdk_config: &aws_config::SdkConfig,
+    role_arns: Vec<String>,
+    unused_access_age: i64,
+    concurrency: usize,
+    ignore_config: IgnoreConfig,
+) -> anyhow::Result<Vec<Finding>> {
+    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(concurrency.max(1)));
+    let mut join_set = tokio::task::JoinSet::new();
+
+    for role_arn in role_arns {
+        let sts_client = sts_client.clone();
+        let sdk_config = sdk_config.clone();
+        let semaphore = semaphore.clone();
+        let ignore_config = ignore_config.clone();
+        join_set.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.expect("semaphore is never closed");
+            let result =
+                analyze_assumed_account(&sts_client, &sdk_config, &role_arn, unused_access_age, ignore_config).await;
+            (role_arn, result)
+        });
+    }
+
+    let mut result = vec![];
+    while let Some(joined) = join_set.join_next().await {
+        let (role_arn, findings) = joined?;
+        match findings {
+            Ok(mut findings) => result.append(&mut findings),
+            Err(e) => eprintln!("warning: skipping account for role {role_arn}: {e:#}"),
+        }
+    }
+    Ok(result)
+}
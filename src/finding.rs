@@ -5,7 +5,7 @@ use aws_sdk_iam::{
 use serde::Serialize;
 use time::{Duration, OffsetDateTime};
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct Finding {
     pub resource: String,
     pub resource_type: ResourceType,
@@ -13,15 +13,44 @@ pub struct Finding {
     pub id: String,
     pub finding_details: Vec<FindingDetails>,
     pub finding_type: FindingType,
+    /// Set after a `--remediate` pass attempts an action against this finding, summarizing what
+    /// was attempted and whether it succeeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+    /// Set to "new" or "resolved" when this `Finding` is part of a `--diff-only` run; absent
+    /// otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff_status: Option<String>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResourceType {
     AwsIamRole,
     AwsIamUser,
 }
 
-#[derive(Serialize, Debug)]
+impl ResourceType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResourceType::AwsIamRole => "AwsIamRole",
+            ResourceType::AwsIamUser => "AwsIamUser",
+        }
+    }
+}
+
+impl std::str::FromStr for ResourceType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "AwsIamRole" => Ok(Self::AwsIamRole),
+            "AwsIamUser" => Ok(Self::AwsIamUser),
+            other => anyhow::bail!("unknown resource type: {other}"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(clippy::enum_variant_names)]
 pub enum FindingType {
     UnusedIamRole,
@@ -30,7 +59,29 @@ pub enum FindingType {
     UnusedPermission,
 }
 
-#[derive(Serialize, Debug)]
+impl FindingType {
+    pub const ALL: [FindingType; 4] = [
+        FindingType::UnusedIamRole,
+        FindingType::UnusedIamUserAccessKey,
+        FindingType::UnusedIamUserPassword,
+        FindingType::UnusedPermission,
+    ];
+
+    pub fn rule_id(&self) -> &'static str {
+        match self {
+            FindingType::UnusedIamRole => "UnusedIamRole",
+            FindingType::UnusedIamUserAccessKey => "UnusedIamUserAccessKey",
+            FindingType::UnusedIamUserPassword => "UnusedIamUserPassword",
+            FindingType::UnusedPermission => "UnusedPermission",
+        }
+    }
+
+    pub fn from_rule_id(rule_id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|ft| ft.rule_id() == rule_id)
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
 #[allow(clippy::enum_variant_names)]
 pub enum FindingDetails {
     UnusedIamRoleDetails(UnusedIamRoleDetails),
@@ -39,26 +90,26 @@ pub enum FindingDetails {
     UnusedPermissionDetails(UnusedPermissionDetails),
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct UnusedIamRoleDetails {
     #[serde(with = "string")]
     pub last_accessed: Option<DateTime>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct UnusedIamUserAccessKeyDetails {
     #[serde(with = "string")]
     pub last_accessed: Option<DateTime>,
     pub access_key_id: String,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct UnusedIamUserPasswordDetails {
     #[serde(with = "string")]
     pub last_accessed: Option<DateTime>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct UnusedPermissionDetails {
     pub actions: Option<Vec<UnusedAction>>,
     pub service_namespace: String,
@@ -66,7 +117,7 @@ pub struct UnusedPermissionDetails {
     pub last_accessed: Option<DateTime>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct UnusedAction {
     pub action: String,
     #[serde(with = "string")]
@@ -94,6 +145,50 @@ impl From<ServiceLastAccessed> for UnusedPermissionDetails {
     }
 }
 
+impl Finding {
+    /// A stable identity for a single `FindingDetails` row belonging to this finding, derived
+    /// from `(resource, finding_type, service_namespace/access_key_id)` rather than the random
+    /// per-run `id`. `analyze_user`/`analyze_role` pack every unused access key (or every unused
+    /// permission) for a resource into one `Finding` with one `FindingDetails` per key/service, so
+    /// identity has to be scoped to the individual detail row rather than the finding as a whole —
+    /// otherwise an unrelated sibling key/service appearing or disappearing would change the hash
+    /// for a key that never changed.
+    pub fn detail_stable_id(&self, detail: &FindingDetails) -> String {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(self.resource.as_bytes());
+        hasher.update(self.finding_type.rule_id().as_bytes());
+        hasher.update(detail.service_or_action().as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+impl FindingDetails {
+    /// The service namespace or access key ID this detail is about, for flattened output formats
+    /// such as CSV where each `Finding` is spread across one row per detail.
+    pub fn service_or_action(&self) -> String {
+        match self {
+            FindingDetails::UnusedIamRoleDetails(_) => String::new(),
+            FindingDetails::UnusedIamUserAccessKeyDetails(d) => d.access_key_id.clone(),
+            FindingDetails::UnusedIamUserPasswordDetails(_) => String::new(),
+            FindingDetails::UnusedPermissionDetails(d) => d.service_namespace.clone(),
+        }
+    }
+
+    pub fn last_accessed(&self) -> Option<DateTime> {
+        match self {
+            FindingDetails::UnusedIamRoleDetails(d) => d.last_accessed,
+            FindingDetails::UnusedIamUserAccessKeyDetails(d) => d.last_accessed,
+            FindingDetails::UnusedIamUserPasswordDetails(d) => d.last_accessed,
+            FindingDetails::UnusedPermissionDetails(d) => d.last_accessed,
+        }
+    }
+
+    pub fn last_accessed_string(&self) -> String {
+        self.last_accessed().map(|d| d.secs().to_string()).unwrap_or_else(|| "never".to_string())
+    }
+}
+
 impl UnusedPermissionDetails {
     pub fn any_not_used(&self, analyzed_at: &OffsetDateTime, unused_access_age: i64) -> bool {
         duration_gt_age(self.last_accessed, analyzed_at, unused_access_age)
@@ -0,0 +1,95 @@
+use serde::Deserialize;
+
+/// TOML-configured allowlist of resources and permissions that should never be reported, even
+/// when they otherwise look unused (break-glass roles, vendor roles, service accounts, etc).
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct IgnoreConfig {
+    #[serde(default, rename = "ignore")]
+    pub rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct IgnoreRule {
+    /// Glob against the full resource ARN, e.g. `arn:aws:iam::*:role/okta-*`.
+    pub arn: Option<String>,
+    /// Prefix match against the IAM path, e.g. `/break-glass/`.
+    pub path_prefix: Option<String>,
+    /// Glob against the resource name, e.g. `okta-*`.
+    pub name: Option<String>,
+    /// Glob against the access advisor `service_namespace`, e.g. `s3`.
+    pub service: Option<String>,
+    /// Glob against an access advisor action name, e.g. `s3:Get*`.
+    pub action: Option<String>,
+}
+
+impl IgnoreConfig {
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    pub fn ignores_resource(&self, arn: &str, path: &str, name: &str) -> bool {
+        self.rules.iter().any(|rule| rule.matches_resource(arn, path, name))
+    }
+
+    pub fn ignores_permission(&self, service_namespace: &str, action: &str) -> bool {
+        self.rules.iter().any(|rule| rule.matches_permission(service_namespace, action))
+    }
+}
+
+impl IgnoreRule {
+    /// A rule matches a resource when every field it sets matches; fields left unset are not
+    /// constraints. This lets an operator combine `path_prefix` and `name` to scope an exception
+    /// narrowly (e.g. "only `emergency-*` roles under `/break-glass/`") instead of each field
+    /// independently exempting anything it matches.
+    fn matches_resource(&self, arn: &str, path: &str, name: &str) -> bool {
+        if self.arn.is_none() && self.path_prefix.is_none() && self.name.is_none() {
+            return false;
+        }
+        self.arn.as_deref().map_or(true, |pattern| glob_match(pattern, arn))
+            && self.path_prefix.as_deref().map_or(true, |prefix| path.starts_with(prefix))
+            && self.name.as_deref().map_or(true, |pattern| glob_match(pattern, name))
+    }
+
+    /// Same all-fields-must-match semantics as `matches_resource`: a rule setting both `service`
+    /// and `action` only ignores that specific action within that service, not the action
+    /// anywhere or the whole service.
+    fn matches_permission(&self, service_namespace: &str, action: &str) -> bool {
+        if self.service.is_none() && self.action.is_none() {
+            return false;
+        }
+        let service_matches = self.service.as_deref().map_or(true, |pattern| glob_match(pattern, service_namespace));
+        let action_matches = match self.action.as_deref() {
+            None => true,
+            Some(pattern) => !action.is_empty() && glob_match(pattern, action),
+        };
+        service_matches && action_matches
+    }
+}
+
+/// Matches `value` against `pattern`, where `*` in `pattern` matches any run of characters
+/// (including none). Splits the pattern on `*` and checks the resulting segments appear in
+/// order: the first segment as a prefix, the last as a suffix, and the rest as ordered substrings.
+fn glob_match(pattern: &str, value: &str) -> bool {
+    if !pattern.contains('*') {
+        return pattern == value;
+    }
+
+    let segments: Vec<&str> = pattern.split('*').collect();
+    let (&first, rest_segments) = segments.split_first().expect("split('*') always yields at least one segment");
+    let (&last, middle_segments) = rest_segments.split_last().unwrap_or((&"", &[]));
+
+    let Some(mut remainder) = value.strip_prefix(first) else {
+        return false;
+    };
+    for &segment in middle_segments {
+        if segment.is_empty() {
+            continue;
+        }
+        match remainder.find(segment) {
+            Some(idx) => remainder = &remainder[idx + segment.len()..],
+            None => return false,
+        }
+    }
+    remainder.ends_with(last)
+}
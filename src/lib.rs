@@ -0,0 +1,8 @@
+pub mod analyzer;
+pub mod finding;
+pub mod ignore;
+pub mod output;
+pub mod remediation;
+pub mod store;
+
+pub use analyzer::{analyze_multi_account, MetaData};
@@ -1,15 +1,20 @@
 use aws_config::{
+    ecs::EcsCredentialsProvider,
     environment::{EnvironmentVariableCredentialsProvider, EnvironmentVariableRegionProvider},
+    imds::credentials::ImdsCredentialsProvider,
     meta::{
         credentials::CredentialsProviderChain,
         region::{ProvideRegion, RegionProviderChain},
     },
+    profile::ProfileFileCredentialsProvider,
     AppName, BehaviorVersion, Region,
 };
 use aws_credential_types::provider::{error::CredentialsError, ProvideCredentials};
 use aws_sdk_iam::config::SharedCredentialsProvider;
-use aws_unused_analyzer::MetaData;
-use clap::Parser;
+use aws_unused_analyzer::{
+    analyze_multi_account, ignore::IgnoreConfig, output, remediation::Remediator, store::FindingStore, MetaData,
+};
+use clap::{ArgAction, Parser};
 
 #[derive(Debug, Parser, Clone)]
 struct Args {
@@ -24,6 +29,69 @@ struct Args {
 
     #[arg(short, long, default_value = "90")]
     unused_access_age: i64,
+
+    /// Named profile from `~/.aws/credentials` / `~/.aws/config` to load credentials from,
+    /// including `source_profile`/`role_arn` assume-role chains and MFA prompts.
+    #[arg(short, long)]
+    profile: Option<String>,
+
+    /// Role ARN of a target account to scan instead of the caller's own account; the caller's
+    /// account is scanned only when no target roles are given. May be passed multiple times for
+    /// Organizations-wide scans.
+    #[arg(long = "role-arn")]
+    role_arns: Vec<String>,
+
+    /// Org-wide role name to assume in each of `--account-id`, e.g. "OrganizationAccountAccessRole".
+    #[arg(long)]
+    org_role_name: Option<String>,
+
+    /// Member account ID to scan by assuming `--org-role-name` into it. May be passed multiple times.
+    #[arg(long = "account-id")]
+    account_ids: Vec<String>,
+
+    /// Maximum number of member accounts to scan concurrently.
+    #[arg(long, default_value_t = 5)]
+    assume_role_concurrency: usize,
+
+    /// Where to send findings: "file", "stdout", or "s3".
+    #[arg(long, default_value = "file")]
+    output: String,
+
+    /// Destination for `--output`: a file path, or an `s3://bucket/key` uri when `--output s3`.
+    #[arg(long)]
+    output_uri: Option<String>,
+
+    /// Output encoding: "json", "csv", or "sarif".
+    #[arg(long, default_value = "json")]
+    format: String,
+
+    /// Path to a SQLite database used to track findings across runs (first_seen/last_seen/resolved_at).
+    #[arg(long)]
+    db: Option<String>,
+
+    /// With `--db`, emit only the findings that are new or newly-resolved since the last run.
+    #[arg(long)]
+    diff_only: bool,
+
+    /// Attempt to remediate findings (deactivate unused access keys, delete unused login
+    /// profiles) instead of only reporting them.
+    #[arg(long)]
+    remediate: bool,
+
+    /// With `--remediate`, only print the planned API calls instead of making them. Pass
+    /// `--dry-run=false` (or `--dry-run false`) to actually perform the remediation.
+    #[arg(long, action = ArgAction::Set, default_value_t = true)]
+    dry_run: bool,
+
+    /// With `--remediate` and `--db`, delete an access key instead of deactivating it once it
+    /// has been flagged as unused for at least this many days.
+    #[arg(long)]
+    remediate_grace_days: Option<i64>,
+
+    /// Path to a TOML file of ignore rules (by ARN glob, path prefix, name, service, or action)
+    /// for resources and permissions that should never be reported.
+    #[arg(long)]
+    config: Option<String>,
 }
 
 impl Args {
@@ -32,6 +100,21 @@ impl Args {
         let sk = self.secret_key.clone().ok_or(anyhow::anyhow!("secret key not found"))?;
         Ok((ak, sk))
     }
+
+    /// All role ARNs to assume for a multi-account scan, combining explicit `--role-arn` values
+    /// with ARNs built from `--org-role-name` x `--account-id`. Empty when neither is set, which
+    /// means "scan only the caller's own account".
+    fn target_role_arns(&self) -> Vec<String> {
+        let mut role_arns = self.role_arns.clone();
+        if let Some(org_role_name) = &self.org_role_name {
+            role_arns.extend(
+                self.account_ids
+                    .iter()
+                    .map(|account_id| format!("arn:aws:iam::{account_id}:role/{org_role_name}")),
+            );
+        }
+        role_arns
+    }
 }
 
 impl ProvideRegion for Args {
@@ -62,6 +145,17 @@ async fn main() -> anyhow::Result<()> {
             .or_else(Region::new("us-east-1"));
         let cred_provider = CredentialsProviderChain::first_try("Args", args.clone())
             .or_else("env", EnvironmentVariableCredentialsProvider::default());
+        let cred_provider = if let Some(profile) = &args.profile {
+            cred_provider.or_else(
+                "profile",
+                ProfileFileCredentialsProvider::builder().profile_name(profile).build(),
+            )
+        } else {
+            cred_provider.or_else("profile", ProfileFileCredentialsProvider::builder().build())
+        };
+        let cred_provider = cred_provider
+            .or_else("imds", ImdsCredentialsProvider::builder().build())
+            .or_else("ecs", EcsCredentialsProvider::builder().build());
 
         let cred_provider = SharedCredentialsProvider::new(cred_provider.provide_credentials().await?);
 
@@ -82,15 +176,58 @@ async fn main() -> anyhow::Result<()> {
 
     let sts_client = aws_sdk_sts::Client::from_conf(aws_sdk_sts::config::Builder::from(&sdk_config).build());
 
-    let owner_account = sts_client.get_caller_identity().send().await?.account.unwrap();
-    let metadata = MetaData {
-        unused_access_age: args.unused_access_age,
-        owner_account,
+    let ignore_config = match &args.config {
+        Some(path) => IgnoreConfig::load(path)?,
+        None => IgnoreConfig::default(),
+    };
+
+    let role_arns = args.target_role_arns();
+    let resp = if role_arns.is_empty() {
+        let owner_account = sts_client.get_caller_identity().send().await?.account.unwrap();
+        let metadata = MetaData {
+            unused_access_age: args.unused_access_age,
+            owner_account,
+            ignore_config,
+        };
+        metadata.analyze(&iam_client).await?
+    } else {
+        analyze_multi_account(
+            &sts_client,
+            &sdk_config,
+            role_arns,
+            args.unused_access_age,
+            args.assume_role_concurrency,
+            ignore_config,
+        )
+        .await?
+    };
+    let store = match &args.db {
+        Some(db_path) => Some(FindingStore::connect(db_path).await?),
+        None => None,
     };
-    let resp = metadata.analyze(&iam_client).await?;
-    // println!("{:#?}", resp);
-    // println!("{:#?}", serde_json::to_string_pretty(&resp));
-    // write resp to file:
-    std::fs::write("unused_findings.json", serde_json::to_string_pretty(&resp)?)?;
+
+    let mut resp = resp;
+    if args.remediate {
+        let remediator = Remediator {
+            dry_run: args.dry_run,
+            delete_access_key_after_days: args.remediate_grace_days,
+        };
+        remediator.remediate(&iam_client, &mut resp, store.as_ref()).await?;
+    }
+
+    let resp = if let Some(store) = &store {
+        let delta = store.record_run(&resp).await?;
+        if args.diff_only {
+            delta.new_findings.into_iter().chain(delta.resolved_findings).collect()
+        } else {
+            resp
+        }
+    } else {
+        resp
+    };
+
+    let format: output::OutputFormat = args.format.parse()?;
+    let sink = output::build_sink(&args.output, args.output_uri.as_deref(), format, &sdk_config)?;
+    sink.emit(&resp).await?;
     Ok(())
 }
@@ -0,0 +1,168 @@
+use std::{path::PathBuf, str::FromStr};
+
+use serde_json::json;
+
+use crate::finding::{Finding, FindingType};
+
+#[derive(Debug, Clone, Copy)]
+pub enum OutputFormat {
+    Json,
+    Csv,
+    Sarif,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "sarif" => Ok(Self::Sarif),
+            other => anyhow::bail!("unsupported output format: {other}"),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+pub trait OutputSink {
+    async fn emit(&self, findings: &[Finding]) -> anyhow::Result<()>;
+}
+
+pub struct FileSink {
+    pub path: PathBuf,
+    pub format: OutputFormat,
+}
+
+#[async_trait::async_trait]
+impl OutputSink for FileSink {
+    async fn emit(&self, findings: &[Finding]) -> anyhow::Result<()> {
+        tokio::fs::write(&self.path, render(findings, self.format)?).await?;
+        Ok(())
+    }
+}
+
+pub struct StdoutSink {
+    pub format: OutputFormat,
+}
+
+#[async_trait::async_trait]
+impl OutputSink for StdoutSink {
+    async fn emit(&self, findings: &[Finding]) -> anyhow::Result<()> {
+        use tokio::io::AsyncWriteExt;
+        tokio::io::stdout().write_all(&render(findings, self.format)?).await?;
+        Ok(())
+    }
+}
+
+pub struct S3Sink {
+    pub client: aws_sdk_s3::Client,
+    pub bucket: String,
+    pub key: String,
+    pub format: OutputFormat,
+}
+
+#[async_trait::async_trait]
+impl OutputSink for S3Sink {
+    async fn emit(&self, findings: &[Finding]) -> anyhow::Result<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(&self.key)
+            .body(render(findings, self.format)?.into())
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+/// Builds the sink selected by `--output` ("file", "stdout", or "s3"), reading its destination
+/// from `output_uri` (a file path, or an `s3://bucket/key` URI).
+pub fn build_sink(
+    output: &str,
+    output_uri: Option<&str>,
+    format: OutputFormat,
+    sdk_config: &aws_config::SdkConfig,
+) -> anyhow::Result<Box<dyn OutputSink>> {
+    match output {
+        "file" => Ok(Box::new(FileSink {
+            path: output_uri.unwrap_or("unused_findings.json").into(),
+            format,
+        })),
+        "stdout" => Ok(Box::new(StdoutSink { format })),
+        "s3" => {
+            let uri = output_uri.ok_or(anyhow::anyhow!("--output-uri is required for --output s3"))?;
+            let (bucket, key) = parse_s3_uri(uri)?;
+            let client = aws_sdk_s3::Client::from_conf(aws_sdk_s3::config::Builder::from(sdk_config).build());
+            Ok(Box::new(S3Sink {
+                client,
+                bucket,
+                key,
+                format,
+            }))
+        }
+        other => anyhow::bail!("unsupported output sink: {other}"),
+    }
+}
+
+fn parse_s3_uri(uri: &str) -> anyhow::Result<(String, String)> {
+    let rest = uri.strip_prefix("s3://").ok_or(anyhow::anyhow!("expected an s3:// uri, got {uri}"))?;
+    let (bucket, key) = rest.split_once('/').ok_or(anyhow::anyhow!("s3 uri is missing an object key: {uri}"))?;
+    Ok((bucket.to_string(), key.to_string()))
+}
+
+fn render(findings: &[Finding], format: OutputFormat) -> anyhow::Result<Vec<u8>> {
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_vec_pretty(findings)?),
+        OutputFormat::Csv => render_csv(findings),
+        OutputFormat::Sarif => Ok(serde_json::to_vec_pretty(&render_sarif(findings))?),
+    }
+}
+
+fn render_csv(findings: &[Finding]) -> anyhow::Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(["resource", "finding_type", "service_or_action", "last_accessed", "diff_status"])?;
+    for finding in findings {
+        for detail in &finding.finding_details {
+            writer.write_record([
+                finding.resource.as_str(),
+                finding.finding_type.rule_id(),
+                detail.service_or_action().as_str(),
+                detail.last_accessed_string().as_str(),
+                finding.diff_status.as_deref().unwrap_or(""),
+            ])?;
+        }
+    }
+    Ok(writer.into_inner()?)
+}
+
+fn render_sarif(findings: &[Finding]) -> serde_json::Value {
+    let rules: Vec<_> = FindingType::ALL.iter().map(|ft| json!({ "id": ft.rule_id() })).collect();
+    let results: Vec<_> = findings
+        .iter()
+        .map(|finding| {
+            let summary = finding
+                .finding_details
+                .iter()
+                .map(|detail| format!("{} last_accessed={}", detail.service_or_action(), detail.last_accessed_string()))
+                .collect::<Vec<_>>()
+                .join("; ");
+            let status = finding.diff_status.as_deref().map(|s| format!(" [{s}]")).unwrap_or_default();
+            json!({
+                "ruleId": finding.finding_type.rule_id(),
+                "message": { "text": format!("unused resource {}{}: {}", finding.resource, status, summary) },
+                "locations": [{
+                    "logicalLocation": { "fullyQualifiedName": finding.resource },
+                }],
+            })
+        })
+        .collect();
+
+    json!({
+        "version": "2.1.0",
+        "runs": [{
+            "tool": { "driver": { "name": "aws-unused-analyzer", "rules": rules } },
+            "results": results,
+        }],
+    })
+}
@@ -0,0 +1,206 @@
+use aws_sdk_iam::{types::StatusType, Client};
+use time::OffsetDateTime;
+
+use crate::{
+    finding::{Finding, FindingDetails, FindingType},
+    store::FindingStore,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemediationOutcome {
+    Applied,
+    DryRun,
+    Flagged,
+    Error,
+}
+
+#[derive(Debug)]
+struct RemediationResult {
+    action: String,
+    outcome: RemediationOutcome,
+    detail: String,
+}
+
+pub struct Remediator {
+    pub dry_run: bool,
+    /// Once an access key's detail row has been in the store for at least this many days (counted
+    /// from its `first_seen`), a later run deletes it outright instead of deactivating it. The
+    /// key's current status is not checked, so a key first recorded by a run without remediation
+    /// can be deleted without an earlier deactivation. `None` means never auto-delete.
+    pub delete_access_key_after_days: Option<i64>,
+}
+
+impl Remediator {
+    /// Remediates every finding in place, consulting `store` (when present) to decide whether an
+    /// access key first recorded there long enough ago has cleared its grace period and should
+    /// now be deleted. Each finding's `remediation` is set to a summary of the action(s) taken, joining
+    /// one summary per `FindingDetails` row when a finding carries more than one (e.g. several
+    /// unused access keys on the same user).
+    pub async fn remediate(
+        &self,
+        iam_client: &Client,
+        findings: &mut [Finding],
+        store: Option<&FindingStore>,
+    ) -> anyhow::Result<()> {
+        for finding in findings.iter_mut() {
+            let mut results = vec![];
+            match finding.finding_type {
+                FindingType::UnusedIamUserAccessKey => {
+                    for detail in &finding.finding_details {
+                        if let FindingDetails::UnusedIamUserAccessKeyDetails(d) = detail {
+                            let detail_id = finding.detail_stable_id(detail);
+                            let past_grace_period = self.past_grace_period(&detail_id, store).await?;
+                            let result = if past_grace_period {
+                                self.delete_access_key(iam_client, finding, &d.access_key_id).await
+                            } else {
+                                self.deactivate_access_key(iam_client, finding, &d.access_key_id).await
+                            };
+                            results.push(result);
+                        }
+                    }
+                }
+                FindingType::UnusedIamUserPassword => {
+                    results.push(self.delete_login_profile(iam_client, finding).await);
+                }
+                FindingType::UnusedIamRole | FindingType::UnusedPermission => {
+                    results.push(RemediationResult {
+                        action: "flag_for_review".to_string(),
+                        outcome: RemediationOutcome::Flagged,
+                        detail: "roles and unused permissions are flagged for manual review, not auto-remediated"
+                            .to_string(),
+                    });
+                }
+            }
+            finding.remediation = Some(
+                results
+                    .iter()
+                    .map(|r| format!("{}: {:?} - {}", r.action, r.outcome, r.detail))
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            );
+        }
+        Ok(())
+    }
+
+    /// `detail_id` must be a per-`FindingDetails` stable ID (see `Finding::detail_stable_id`), not
+    /// the aggregated finding's identity, so that one access key's grace-period clock can't be
+    /// reset by an unrelated sibling key on the same user.
+    async fn past_grace_period(&self, detail_id: &str, store: Option<&FindingStore>) -> anyhow::Result<bool> {
+        let (Some(grace_days), Some(store)) = (self.delete_access_key_after_days, store) else {
+            return Ok(false);
+        };
+        let Some(first_seen) = store.first_seen(detail_id).await? else {
+            return Ok(false);
+        };
+        let age_days = (OffsetDateTime::now_utc().unix_timestamp() - first_seen) / (24 * 60 * 60);
+        Ok(age_days >= grace_days)
+    }
+
+    async fn deactivate_access_key(&self, iam_client: &Client, finding: &Finding, access_key_id: &str) -> RemediationResult {
+        let Some(user_name) = user_name_from_arn(&finding.resource) else {
+            return RemediationResult {
+                action: "update_access_key".to_string(),
+                outcome: RemediationOutcome::Error,
+                detail: format!("could not extract user name from arn: {}", finding.resource),
+            };
+        };
+        let action = format!("update_access_key(user={user_name}, access_key_id={access_key_id}, status=Inactive)");
+        if self.dry_run {
+            return RemediationResult {
+                action,
+                outcome: RemediationOutcome::DryRun,
+                detail: "dry run: no changes made".to_string(),
+            };
+        }
+        match iam_client
+            .update_access_key()
+            .user_name(user_name)
+            .access_key_id(access_key_id)
+            .status(StatusType::Inactive)
+            .send()
+            .await
+        {
+            Ok(_) => RemediationResult {
+                action,
+                outcome: RemediationOutcome::Applied,
+                detail: "access key deactivated".to_string(),
+            },
+            Err(e) => RemediationResult {
+                action,
+                outcome: RemediationOutcome::Error,
+                detail: e.to_string(),
+            },
+        }
+    }
+
+    async fn delete_access_key(&self, iam_client: &Client, finding: &Finding, access_key_id: &str) -> RemediationResult {
+        let Some(user_name) = user_name_from_arn(&finding.resource) else {
+            return RemediationResult {
+                action: "delete_access_key".to_string(),
+                outcome: RemediationOutcome::Error,
+                detail: format!("could not extract user name from arn: {}", finding.resource),
+            };
+        };
+        let action = format!("delete_access_key(user={user_name}, access_key_id={access_key_id})");
+        if self.dry_run {
+            return RemediationResult {
+                action,
+                outcome: RemediationOutcome::DryRun,
+                detail: "dry run: no changes made".to_string(),
+            };
+        }
+        match iam_client
+            .delete_access_key()
+            .user_name(user_name)
+            .access_key_id(access_key_id)
+            .send()
+            .await
+        {
+            Ok(_) => RemediationResult {
+                action,
+                outcome: RemediationOutcome::Applied,
+                detail: "access key deleted".to_string(),
+            },
+            Err(e) => RemediationResult {
+                action,
+                outcome: RemediationOutcome::Error,
+                detail: e.to_string(),
+            },
+        }
+    }
+
+    async fn delete_login_profile(&self, iam_client: &Client, finding: &Finding) -> RemediationResult {
+        let Some(user_name) = user_name_from_arn(&finding.resource) else {
+            return RemediationResult {
+                action: "delete_login_profile".to_string(),
+                outcome: RemediationOutcome::Error,
+                detail: format!("could not extract user name from arn: {}", finding.resource),
+            };
+        };
+        let action = format!("delete_login_profile(user={user_name})");
+        if self.dry_run {
+            return RemediationResult {
+                action,
+                outcome: RemediationOutcome::DryRun,
+                detail: "dry run: no changes made".to_string(),
+            };
+        }
+        match iam_client.delete_login_profile().user_name(user_name).send().await {
+            Ok(_) => RemediationResult {
+                action,
+                outcome: RemediationOutcome::Applied,
+                detail: "login profile deleted".to_string(),
+            },
+            Err(e) => RemediationResult {
+                action,
+                outcome: RemediationOutcome::Error,
+                detail: e.to_string(),
+            },
+        }
+    }
+}
+
+fn user_name_from_arn(arn: &str) -> Option<&str> {
+    // IAM resource names never contain '/', even with a path prefix, so the last segment is it.
+    arn.rsplit('/').next().filter(|name| !name.is_empty())
+}
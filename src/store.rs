@@ -0,0 +1,181 @@
+use sqlx::{sqlite::SqlitePoolOptions, Row, SqlitePool};
+use time::OffsetDateTime;
+
+use crate::finding::{
+    Finding, FindingDetails, FindingType, ResourceType, UnusedIamRoleDetails, UnusedIamUserAccessKeyDetails,
+    UnusedIamUserPasswordDetails, UnusedPermissionDetails,
+};
+
+/// New and newly-resolved findings produced by a single `FindingStore::record_run`, for
+/// `--diff-only` runs that should only surface what changed since the last run. Resolved findings
+/// are reconstructed from stored columns (they carry only the one `FindingDetails` row that
+/// actually resolved, not the full set the original finding may have had) and tagged via
+/// `Finding::diff_status` so both can be rendered through the normal `OutputSink` machinery.
+#[derive(Debug, Default)]
+pub struct FindingDelta {
+    pub new_findings: Vec<Finding>,
+    pub resolved_findings: Vec<Finding>,
+}
+
+pub struct FindingStore {
+    pool: SqlitePool,
+}
+
+impl FindingStore {
+    pub async fn connect(db_path: &str) -> anyhow::Result<Self> {
+        let pool = SqlitePoolOptions::new()
+            .connect(&format!("sqlite://{db_path}?mode=rwc"))
+            .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS findings (
+                stable_id TEXT PRIMARY KEY,
+                resource TEXT NOT NULL,
+                resource_type TEXT NOT NULL,
+                resource_owner_account TEXT NOT NULL,
+                finding_type TEXT NOT NULL,
+                detail_key TEXT NOT NULL,
+                first_seen INTEGER NOT NULL,
+                last_seen INTEGER NOT NULL,
+                resolved_at INTEGER
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+
+    /// `first_seen` (as a unix timestamp) of the detail row with the given per-detail stable ID
+    /// (see `Finding::detail_stable_id`), if it has ever been recorded. Used to gate staged
+    /// remediation on a grace period.
+    pub async fn first_seen(&self, stable_id: &str) -> anyhow::Result<Option<i64>> {
+        let row = sqlx::query("SELECT first_seen FROM findings WHERE stable_id = ?")
+            .bind(stable_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|row| row.get::<i64, _>("first_seen")))
+    }
+
+    /// Diffs `findings` against history at the granularity of individual `FindingDetails` rows
+    /// (one access key, one service, etc), since a `Finding` packs every unused detail for a
+    /// resource into one struct and that set can grow or shrink across runs without any
+    /// individual detail's own history changing. Inserts new rows with `first_seen`/`last_seen`,
+    /// bumps `last_seen` (and clears `resolved_at`) for ones still present, and marks rows absent
+    /// from this run as `resolved_at = now`.
+    pub async fn record_run(&self, findings: &[Finding]) -> anyhow::Result<FindingDelta> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let mut delta = FindingDelta::default();
+
+        for finding in findings {
+            let mut new_details = vec![];
+            for detail in &finding.finding_details {
+                let stable_id = finding.detail_stable_id(detail);
+                let existing = sqlx::query("SELECT 1 FROM findings WHERE stable_id = ?")
+                    .bind(&stable_id)
+                    .fetch_optional(&self.pool)
+                    .await?;
+                if existing.is_some() {
+                    sqlx::query("UPDATE findings SET last_seen = ?, resolved_at = NULL WHERE stable_id = ?")
+                        .bind(now)
+                        .bind(&stable_id)
+                        .execute(&self.pool)
+                        .await?;
+                } else {
+                    sqlx::query(
+                        "INSERT INTO findings
+                            (stable_id, resource, resource_type, resource_owner_account, finding_type, detail_key, first_seen, last_seen, resolved_at)
+                         VALUES (?, ?, ?, ?, ?, ?, ?, ?, NULL)",
+                    )
+                    .bind(&stable_id)
+                    .bind(&finding.resource)
+                    .bind(finding.resource_type.as_str())
+                    .bind(&finding.resource_owner_account)
+                    .bind(finding.finding_type.rule_id())
+                    .bind(detail.service_or_action())
+                    .bind(now)
+                    .bind(now)
+                    .execute(&self.pool)
+                    .await?;
+                    new_details.push(detail.clone());
+                }
+            }
+            if !new_details.is_empty() {
+                delta.new_findings.push(Finding {
+                    finding_details: new_details,
+                    diff_status: Some("new".to_string()),
+                    ..finding.clone()
+                });
+            }
+        }
+
+        let seen_ids: Vec<String> = findings
+            .iter()
+            .flat_map(|finding| finding.finding_details.iter().map(|detail| finding.detail_stable_id(detail)))
+            .collect();
+        let unresolved_query = if seen_ids.is_empty() {
+            "SELECT * FROM findings WHERE resolved_at IS NULL".to_string()
+        } else {
+            let placeholders = vec!["?"; seen_ids.len()].join(", ");
+            format!("SELECT * FROM findings WHERE resolved_at IS NULL AND stable_id NOT IN ({placeholders})")
+        };
+        let mut query = sqlx::query(&unresolved_query);
+        for id in &seen_ids {
+            query = query.bind(id);
+        }
+        let newly_absent = query.fetch_all(&self.pool).await?;
+
+        for row in &newly_absent {
+            let stable_id: String = row.get("stable_id");
+            sqlx::query("UPDATE findings SET resolved_at = ? WHERE stable_id = ?")
+                .bind(now)
+                .bind(&stable_id)
+                .execute(&self.pool)
+                .await?;
+            delta.resolved_findings.push(reconstruct_finding(row));
+        }
+
+        Ok(delta)
+    }
+}
+
+/// Rebuilds a minimal `Finding` carrying just the one `FindingDetails` row that resolved, from a
+/// stored `findings` row. The rebuilt finding has a fresh random `id` (the original run's `id` was
+/// never stored) and is tagged `diff_status: Some("resolved")`.
+fn reconstruct_finding(row: &sqlx::sqlite::SqliteRow) -> Finding {
+    let resource: String = row.get("resource");
+    let resource_type: String = row.get("resource_type");
+    let resource_owner_account: String = row.get("resource_owner_account");
+    let finding_type: String = row.get("finding_type");
+    let detail_key: String = row.get("detail_key");
+
+    let resource_type = resource_type.parse().unwrap_or(ResourceType::AwsIamUser);
+    let finding_type = FindingType::from_rule_id(&finding_type).unwrap_or(FindingType::UnusedPermission);
+
+    Finding {
+        resource,
+        resource_type,
+        resource_owner_account,
+        id: uuid::Uuid::new_v4().to_string(),
+        finding_details: vec![placeholder_detail(finding_type, &detail_key)],
+        finding_type,
+        remediation: None,
+        diff_status: Some("resolved".to_string()),
+    }
+}
+
+fn placeholder_detail(finding_type: FindingType, detail_key: &str) -> FindingDetails {
+    match finding_type {
+        FindingType::UnusedIamRole => FindingDetails::UnusedIamRoleDetails(UnusedIamRoleDetails { last_accessed: None }),
+        FindingType::UnusedIamUserAccessKey => FindingDetails::UnusedIamUserAccessKeyDetails(UnusedIamUserAccessKeyDetails {
+            last_accessed: None,
+            access_key_id: detail_key.to_string(),
+        }),
+        FindingType::UnusedIamUserPassword => {
+            FindingDetails::UnusedIamUserPasswordDetails(UnusedIamUserPasswordDetails { last_accessed: None })
+        }
+        FindingType::UnusedPermission => FindingDetails::UnusedPermissionDetails(UnusedPermissionDetails {
+            actions: None,
+            service_namespace: detail_key.to_string(),
+            last_accessed: None,
+        }),
+    }
+}